@@ -0,0 +1,106 @@
+//! Structural assertions about a `lopdf::Document`.
+//!
+//! Build a `PdfPredicate` with the `expect_*` methods and run it with `check`, either from the
+//! `check` CLI command or from this crate's own integration tests (e.g. to confirm that `burst`
+//! produced single-page files whose `MediaBox` matches the source page).
+
+use chrono::{DateTime, FixedOffset};
+
+use lopdf::Document;
+
+use errors::*;
+use pdf::*;
+
+/// The default tolerance, in points, for `MediaBox` comparisons.
+pub const DEFAULT_TOLERANCE: f64 = 1.0;
+
+/// A set of structural expectations to check against a `Document`.
+///
+/// Build one with `PdfPredicate::new()` and its `expect_*` methods, then run it with `check`.
+#[derive(Debug, Default)]
+pub struct PdfPredicate {
+    page_count: Option<usize>,
+    page_sizes: Vec<(u32, f64, f64)>,
+    creation_date: Option<DateTime<FixedOffset>>,
+    mod_date: Option<DateTime<FixedOffset>>,
+    tolerance: f64,
+}
+
+impl PdfPredicate {
+    /// A predicate with no expectations set, and the default size tolerance.
+    pub fn new() -> Self {
+        PdfPredicate {
+            tolerance: DEFAULT_TOLERANCE,
+            ..Default::default()
+        }
+    }
+
+    /// Override the tolerance (in points) used by `expect_page_size`.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Expect the document to have exactly `count` pages.
+    pub fn expect_page_count(mut self, count: usize) -> Self {
+        self.page_count = Some(count);
+        self
+    }
+
+    /// Expect `page` (1-indexed, matching `lopdf::Document::get_pages`) to have the given
+    /// `MediaBox` dimensions, in points.
+    pub fn expect_page_size(mut self, page: u32, width: f64, height: f64) -> Self {
+        self.page_sizes.push((page, width, height));
+        self
+    }
+
+    /// Expect the document's `/Info` `CreationDate` to match `date`.
+    pub fn expect_creation_date(mut self, date: DateTime<FixedOffset>) -> Self {
+        self.creation_date = Some(date);
+        self
+    }
+
+    /// Expect the document's `/Info` `ModDate` to match `date`.
+    pub fn expect_mod_date(mut self, date: DateTime<FixedOffset>) -> Self {
+        self.mod_date = Some(date);
+        self
+    }
+
+    /// Check `doc` against every expectation that's been set, failing on the first mismatch.
+    pub fn check(&self, doc: &Document) -> Result<()> {
+        if let Some(expected) = self.page_count {
+            let actual = page_count(doc);
+            if actual != expected {
+                return Err(format!("Expected {} pages, found {}", expected, actual).into());
+            }
+        }
+
+        for &(page, width, height) in &self.page_sizes {
+            let (w, h) = page_size(doc, page)?;
+            if !(approx_eq(w, width, self.tolerance) && approx_eq(h, height, self.tolerance)) {
+                return Err(format!(
+                    "Page {} size {}×{} didn’t match expected {}×{} (tolerance {})",
+                    page, w, h, width, height, self.tolerance
+                ).into());
+            }
+        }
+
+        if let Some(ref expected) = self.creation_date {
+            check_date("CreationDate", creation_date(doc)?, expected)?;
+        }
+
+        if let Some(ref expected) = self.mod_date {
+            check_date("ModDate", mod_date(doc)?, expected)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_date(key: &str, actual: DateTime<FixedOffset>, expected: &DateTime<FixedOffset>) -> Result<()> {
+    if actual != *expected {
+        return Err(format!("{} {} didn’t match expected {}", key, actual, expected).into());
+    }
+
+    Ok(())
+}
@@ -1,15 +1,16 @@
 //! Select pages from pdf(s) and concatenate into a single output pdf
 
-use std::{borrow::Cow, iter, ops::RangeInclusive, str, string::String};
+use std::{ffi::OsStr, fmt, fmt::Display, fmt::Write as _, ops::RangeInclusive, string::String};
 
-use chrono::{DateTime, NaiveDateTime};
-use itertools::{Itertools, MinMaxResult};
-use xmltree::Element;
+use chrono::{DateTime, FixedOffset};
+use itertools::Itertools;
 
 use lopdf::*;
 
+use check::PdfPredicate;
 use common::*;
 use errors::*;
+use pdf::*;
 
 /// The arguments supplied to the `sel` and `zip` commands.
 pub type InputInOut = InOut<PDFName>;
@@ -20,6 +21,22 @@ pub type InputInOut = InOut<PDFName>;
 pub struct InOut<A> {
     pub inputs: Vec<PDFPages<A>>,
     pub outfile: PDFName,
+    /// Restrict the selection to pages matching this `(width, height)`, in points, within
+    /// `SIZE_TOLERANCE`. Only honoured by `sel`; `zip` ignores it.
+    pub size_filter: Option<(f64, f64)>,
+    /// Fail on a free or invalid object reference while extracting a page, rather than silently
+    /// treating it as absent (see `TreeOptions`).
+    pub strict: bool,
+}
+
+impl<A: Display> Display for InOut<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inputs
+            .iter()
+            .map(|i| write!(f, " {}", i))
+            .collect::<fmt::Result>()?;
+        write!(f, " output {}", self.outfile)
+    }
 }
 
 #[derive(Debug)]
@@ -62,19 +79,268 @@ impl<A> PDFPages<A> {
     }
 }
 
+impl<A: Display> Display for PDFPages<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, " {}", self.file)?;
+        self.page_ranges
+            .iter()
+            .cloned()
+            .map(RangeInclusive::into_inner)
+            .map(|(fr, to)| write!(f, " {}-{}", fr, to))
+            .collect()
+    }
+}
+
 impl PDFPages<PDFName> {
     fn load_doc(self) -> Option<PDFPages<Document>> {
         self.traverse(|x| x.load_doc()).ok()
     }
 }
 
+/// Resolve a `PDFPages`’ `page_ranges` against a loaded document’s real page numbers.
+///
+/// An empty `page_ranges` means the whole document. Ranges that extend beyond the document’s
+/// own `page_range` are clamped to it; a range that doesn’t overlap the document at all is an
+/// error.
+fn resolve_page_list(doc: &Document, ranges: &[RangeInclusive<usize>]) -> Result<Vec<u32>> {
+    let pages = doc.get_pages();
+
+    if ranges.is_empty() {
+        return Ok(pages.keys().cloned().collect());
+    }
+
+    let (min, max) = page_range(doc)?.into_inner();
+
+    ranges
+        .iter()
+        .map(|r| {
+            let from = *r.start() as u32;
+            let to = *r.end() as u32;
+
+            if to < min || from > max {
+                return Err(format!(
+                    "Page range {}-{} lies outside the document’s page range {}-{}",
+                    from, to, min, max
+                ).into());
+            }
+
+            Ok(from.max(min)..=to.min(max))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(|rs| rs.into_iter().flatten().collect())
+}
+
+/// Deep-copy a page (and its inherited `MediaBox`) from `doc` into `new`, appending it to `kids`
+/// and recording the page's source → target id in `kept`, so `copy_outline_items` can remap
+/// outline destinations against it afterwards.
+///
+/// `options` controls how the page's subtree handles references to free or absent objects (see
+/// `TreeOptions`).
+fn copy_page_into(
+    doc: &Document,
+    oid: ObjectId,
+    new: &mut Document,
+    pages_id: ObjectId,
+    kids: &mut Vec<Object>,
+    kept: &mut IdRemap,
+    options: TreeOptions,
+) -> Result<()> {
+    let mb = media_box(doc, oid)?;
+
+    let new_page = PDFTree::new_with(oid, doc, options)?;
+    let page_id = new_page.link_reference(new);
+
+    new.get_object_mut(page_id)
+        .and_then(Object::as_dict_mut)
+        .map(|d| {
+            d.set("Parent", pages_id);
+            d.set("MediaBox", mb);
+        });
+
+    kids.push(page_id.into());
+    kept.insert(oid, page_id);
+
+    Ok(())
+}
+
+/// Wrap the collected page `kids` in a single `Pages`/`Catalog` pair and set it as the trailer
+/// `Root`, ready to `save`. `outlines_id` is attached as the catalog's `/Outlines`, if the inputs
+/// had any outline items that survived extraction (see `merge_outlines`).
+fn finalize_pages(
+    new: &mut Document,
+    pages_id: ObjectId,
+    kids: Vec<Object>,
+    outlines_id: Option<ObjectId>,
+) {
+    let count = kids.len() as i64;
+
+    new.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+        }),
+    );
+
+    let mut catalog = dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    };
+
+    if let Some(outlines_id) = outlines_id {
+        catalog.set("Outlines", outlines_id);
+    }
+
+    let catalog_id = new.add_object(catalog);
+
+    new.trailer.set("Root", catalog_id);
+
+    new.compress();
+}
+
+/// Finalize the merged `/Outlines` tree at `outlines_id` from `items`, the outline items already
+/// copied (and, when merging several inputs, concatenated) by `copy_outline_items`.
+///
+/// Returns `outlines_id` for `finalize_pages` to attach to the catalog, or `None` if no input
+/// contributed a surviving outline item, leaving `outlines_id` an unused reserved id.
+fn merge_outlines(new: &mut Document, outlines_id: ObjectId, items: Vec<ObjectId>) -> Option<ObjectId> {
+    if items.is_empty() {
+        return None;
+    }
+
+    new.objects
+        .insert(outlines_id, Object::Dictionary(dictionary! { "Type" => "Outlines" }));
+    link_outline_siblings(new, outlines_id, &items);
+
+    Some(outlines_id)
+}
+
 /// Run the input
 pub fn sel(input: InputInOut) -> Result<()> {
-    //let sels = load_docs(input);
+    let InOut { inputs, outfile, size_filter, strict } = input;
+
+    let options = if strict { TreeOptions::new().strict() } else { TreeOptions::new() };
+
+    let mut new = Document::new();
+    let pages_id = new.new_object_id();
+    let outlines_id = new.new_object_id();
+    let mut kids = vec![];
+    let mut outline_items = vec![];
+
+    for spec in inputs {
+        let PDFPages { file: doc, page_ranges } = spec
+            .load_doc()
+            .error("Couldn’t load input document")?;
+
+        let pages = doc.get_pages();
+        let mut kept = IdRemap::new();
+
+        let nos = resolve_page_list(&doc, &page_ranges)?;
+        let nos = match size_filter {
+            Some((w, h)) => filter_by_size(&doc, &nos, w, h, SIZE_TOLERANCE),
+            None => nos,
+        };
+
+        for no in nos {
+            let oid = *pages.get(&no).error("Couldn’t locate page in document")?;
+            copy_page_into(&doc, oid, &mut new, pages_id, &mut kids, &mut kept, options)?;
+        }
+
+        if let Some(first) = outline_first_item(&doc) {
+            outline_items.extend(copy_outline_items(&doc, &mut new, &kept, first, outlines_id));
+        }
+    }
+
+    let outlines_id = merge_outlines(&mut new, outlines_id, outline_items);
+    finalize_pages(&mut new, pages_id, kids, outlines_id);
 
-    //Ok(Document::new());
+    outfile
+        .over(|p| new.save(p))
+        .chain_err(|| "Couldn’t save file")?;
 
-    Ok::<_, Error>(())
+    Ok(())
+}
+
+/// Interleave pages from multiple inputs round-robin: page 1 of input 0, page 1 of input 1, …,
+/// then page 2 of input 0, and so on, honouring each input’s resolved `page_ranges`.
+///
+/// This is the standard fix for duplex scanning, where one pass produces the odd pages and a
+/// second (often reversed) pass produces the even pages: zipping them back together reconstructs
+/// the original document. Inputs with unequal page counts simply run out and are skipped over;
+/// rounds continue until every input is exhausted, so pages from the longer inputs are still
+/// emitted at the end.
+pub fn zip(input: InputInOut) -> Result<()> {
+    let InOut { inputs, outfile, strict, .. } = input;
+
+    let options = if strict { TreeOptions::new().strict() } else { TreeOptions::new() };
+
+    let loaded = inputs
+        .into_iter()
+        .map(|spec| spec.load_doc().error("Couldn’t load input document"))
+        .collect::<Result<Vec<_>>>()?;
+
+    let page_lists = loaded
+        .iter()
+        .map(|p| resolve_page_list(&p.file, &p.page_ranges))
+        .collect::<Result<Vec<_>>>()?;
+
+    let max_len = page_lists.iter().map(Vec::len).max().unwrap_or(0);
+
+    let mut new = Document::new();
+    let pages_id = new.new_object_id();
+    let outlines_id = new.new_object_id();
+    let mut kids = vec![];
+    let mut kept = loaded.iter().map(|_| IdRemap::new()).collect::<Vec<_>>();
+
+    for i in 0..max_len {
+        for ((spec, list), kept) in loaded.iter().zip(page_lists.iter()).zip(kept.iter_mut()) {
+            if let Some(&no) = list.get(i) {
+                let pages = spec.file.get_pages();
+                let oid = *pages.get(&no).error("Couldn’t locate page in document")?;
+                copy_page_into(&spec.file, oid, &mut new, pages_id, &mut kids, kept, options)?;
+            }
+        }
+    }
+
+    let mut outline_items = vec![];
+    for (spec, kept) in loaded.iter().zip(kept.iter()) {
+        if let Some(first) = outline_first_item(&spec.file) {
+            outline_items.extend(copy_outline_items(&spec.file, &mut new, kept, first, outlines_id));
+        }
+    }
+
+    let outlines_id = merge_outlines(&mut new, outlines_id, outline_items);
+    finalize_pages(&mut new, pages_id, kids, outlines_id);
+
+    outfile
+        .over(|p| new.save(p))
+        .chain_err(|| "Couldn’t save file")?;
+
+    Ok(())
+}
+
+/// Tolerance, in points, for treating two `MediaBox` dimensions as the same physical page size.
+const SIZE_TOLERANCE: f64 = 1.0;
+
+/// The (width, height) shared by the most pages in `dims`, used as the document’s “dominant”
+/// page size so outliers can be flagged.
+fn dominant_size(dims: &[(u32, (f64, f64))]) -> (f64, f64) {
+    use std::collections::HashMap;
+
+    let mut counts: HashMap<(i64, i64), (usize, (f64, f64))> = HashMap::new();
+
+    for &(_, (w, h)) in dims {
+        let key = (w.round() as i64, h.round() as i64);
+        let entry = counts.entry(key).or_insert((0, (w, h)));
+        entry.0 += 1;
+    }
+
+    counts
+        .values()
+        .max_by_key(|&&(count, _)| count)
+        .map(|&(_, size)| size)
+        .unwrap_or((0.0, 0.0))
 }
 
 /// Display metadata
@@ -113,121 +379,203 @@ pub fn info(input: &[PDFName]) -> Result<()> {
     docs.map(|doc| -> Result<()> {
         // TODO print file name!
 
-        let i = get_trail_info(&doc)?;
+        // Not every document carries a classic `/Info` dict — some only have XMP metadata.
+        if let Ok(i) = get_trail_info(&doc) {
+            i.filter_map(|(k, v)| {
+                let d = display_object(&doc, v).ok()?;
+                Some((k, d))
+            }).for_each(|(k, v)| println!("{}: {}", k, v));
+        }
 
-        i.filter_map(|(k, v)| {
-            let d = display_object(&doc, v).ok()?;
-            Some((k, d))
-        }).for_each(|(k, v)| println!("{}: {}", k, v));
+        let xmp = get_metadata(&doc);
+
+        if !xmp.is_empty() {
+            println!("XMP:");
+            xmp.iter().for_each(|(k, v)| println!("  {}: {}", k, v));
+        }
 
         let p = page_range(&doc).map(RangeInclusive::into_inner)?;
 
         println!("Pages: {}–{}", p.0, p.1);
 
+        let dims = doc
+            .get_pages()
+            .iter()
+            .map(|(&no, &oid)| -> Result<(u32, (f64, f64))> {
+                let mb = media_box(&doc, oid)?;
+                Ok((no, media_box_dimensions(&mb)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (dw, dh) = dominant_size(&dims);
+
+        for (no, (w, h)) in &dims {
+            let flag = if approx_eq(*w, dw, SIZE_TOLERANCE) && approx_eq(*h, dh, SIZE_TOLERANCE) {
+                ""
+            } else {
+                " (differs from the document’s dominant page size)"
+            };
+
+            println!("Page {} size: {}×{} pt{}", no, w, h, flag);
+        }
+
         Ok(())
     }).for_each(drop);
 
     Ok(())
 }
 
-fn display_trail_date(s: &str) -> Result<String> {
-    DateTime::parse_from_str(&(s.replace("'", "").replace("Z", "+")), "D:%Y%m%d%H%M%S%z")
-        .map(|d| format!("{}", d.format("%a, %d %b %Y %T %z")))
-        .or_else(|_| {
-            NaiveDateTime::parse_from_str(s, "D:%Y%m%d%H%M%S")
-                .map(|d| format!("{}", d.format("%a, %d %b %Y %T")))
-        })
-        .chain_err(|| "Couldn’t parse date")
-}
+/// Burst pdf files into individual pages, named as the original, with a numerical suffix.
+///
+/// `strict` controls how page extraction handles references to free or absent objects (see
+/// `TreeOptions`).
+pub fn burst(input: &[PDFName], strict: bool) -> Result<()> {
+    let options = if strict { TreeOptions::new().strict() } else { TreeOptions::new() };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_display_trail_date() {
-        assert_eq!(
-            display_trail_date("D:20170712171035+01'00'").unwrap_or_else(|e| format!("{:?}", e)),
-            "Wed, 12 Jul 2017 17:10:35 +0100"
-        );
-        assert_eq!(
-            display_trail_date("D:20170711121931").unwrap_or_else(|e| format!("{:?}", e)),
-            "Tue, 11 Jul 2017 12:19:31"
-        );
-        assert_eq!(
-            display_trail_date("D:20180710153507Z00'00'").unwrap_or_else(|e| format!("{:?}", e)),
-            "Tue, 10 Jul 2018 15:35:07 +0000"
-        );
-    }
-}
+    let docs = input
+        .iter()
+        .filter_map(|name| name.load_doc().ok().map(|doc| (name, doc)));
 
-fn get_trail_info(doc: &Document) -> Result<impl Iterator<Item = (&str, &Object)>> {
-    let trail = &doc.trailer;
+    docs.map(|(name, doc)| -> Result<()> {
+        println!();
+        println!("File: {}", &name);
 
-    let info = trail
-        .get("Info")
-        .and_then(Object::as_reference)
-        .error("Couldn’t identify pdf info")
-        .and_then(|r| doc.get_dictionary(r).error("Couldn’t access pdf info"))?;
+        let pages = doc.get_pages();
 
-    Ok(info.iter().map(|(s, o)| (&s[..], o)))
+        let pp = page_range(&doc)?;
+
+        let max_pages = match pp.into_inner() {
+            (s, e) => {
+                // Check underflow
+                debug_assert!(e >= s);
+                e + 1 - s
+            }
+        };
+
+        let print_suffix_width = f64::ceil(f64::log10(max_pages as f64)) as usize;
+
+        let name_prefix =
+            name.over(|p| p.file_stem().unwrap_or_else(|| OsStr::new("")).to_os_string());
+
+        // Prefix and suffix, plus `_` and ".pdf"
+        // TODO check overflow?
+        let print_name_width = name_prefix.len() + print_suffix_width + 5;
+
+        pages
+            .iter()
+            .map(|(no, &oid)| -> Result<()> {
+                println!("Page {}", no);
+
+                let mut new = Document::new();
+                let pages_id = new.new_object_id();
+                let mut kids = vec![];
+                let mut kept = IdRemap::new();
+
+                copy_page_into(&doc, oid, &mut new, pages_id, &mut kids, &mut kept, options)?;
+
+                finalize_pages(&mut new, pages_id, kids, None);
+
+                // Could just use format! here but given we already know the size of the name, why not
+                // do it explicitly.
+                let mut new_name = String::with_capacity(print_name_width);
+                write!(
+                    new_name,
+                    "{}_{:0width$}.pdf",
+                    name_prefix.to_string_lossy(),
+                    no,
+                    width = print_suffix_width
+                ).chain_err(|| "Couldn’t construct filename")?;
+
+                new.save(new_name).chain_err(|| "Couldn’t save file")?;
+
+                Ok(())
+            })
+            .for_each(drop);
+
+        Ok(())
+    }).for_each(drop);
+
+    Ok(())
 }
 
-fn get_metadata(doc: &Document) -> Result<Vec<(String, String)>> {
-    let catalog = doc.catalog().error("Couldn’t access catalog")?;
-
-    let metadata = catalog
-        .get("Metadata")
-        .and_then(Object::as_reference)
-        .error("Couldn’t identify metadata")
-        .and_then(|r| {
-            doc.get_object(r)
-                .and_then(Object::as_stream)
-                .error("Couldn’t access metadata")
-        })
-        .map(|s| &s.content)
-        //.and_then(|s| str::from_utf8(&s[54..]).error("Couldn’t decode utf8"))?;
-        .and_then(|s| Element::parse(&s[54..]).chain_err(|| "Couldn’t read xml"))
-        .map(|e| text_names(&e).into_iter().map(|(n,t)| (n.into_owned(), t.into_owned())).collect())?;
-
-    /*
-     *    fn decode_stream(s: &Stream) -> Result<content::Content> {
-     *        s.decode_content().error("Couldn’t parse content stream")
-     *    }
-     *
-     *    fn chain_leaves<A>(e: &Element) -> impl Iterator<Item = &Element> {
-     *        match e.children[..] {
-     *            [] => iter::once(e),
-     *            //ref cs => cs.iter().fold(iter::empty(), |a, c| a.chain(chain_leaves(c)).collect()),
-     *            // TODO
-     *            ref cs => cs.iter().flat_map(|it| it.clone()a.chain(chain_leaves(c)).collect()),
-     *        }
-     *    }
-     */
-
-    fn fold_element_leaves<'a, A>(e: &'a Element, f: impl Fn(&'a Element) -> A) -> Vec<A> {
-        match e.children[..] {
-            [] => vec![f(e)],
-            ref cs => cs
-                .iter()
-                .fold(vec![], |a: Vec<A>, c: &Element| fold_element_leaves(c, &f)),
+/// Values that can be written into a document’s `Info` dictionary by `set_info`.
+///
+/// A field left `None` leaves the corresponding `Info` entry untouched.
+#[derive(Debug, Default)]
+pub struct InfoUpdate {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creation_date: Option<DateTime<FixedOffset>>,
+    pub mod_date: Option<DateTime<FixedOffset>>,
+}
+
+/// Write document metadata and pdf dates into `file`’s `Info` dictionary, creating it (and
+/// linking it from the trailer) if the document doesn’t already have one, then save.
+///
+/// Round-tripping `info` → `set_info` → `info` is stable, since the dates are written with
+/// `format_trail_date`, the exact inverse of the parsing `display_trail_date` already performs.
+pub fn set_info(file: &PDFName, update: InfoUpdate) -> Result<()> {
+    let mut doc = file.load_doc()?;
+
+    let info_id = match doc.trailer.get("Info").and_then(Object::as_reference) {
+        Ok(id) => id,
+        Err(_) => {
+            let id = doc.add_object(Dictionary::new());
+            doc.trailer.set("Info", id);
+            id
         }
-    }
+    };
 
-    fn text_names<'a>(el: &'a Element) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
-        fold_element_leaves(el, text_name)
-            .into_iter()
-            .filter_map(|x| x)
-            .collect()
-    }
+    let info = doc
+        .get_object_mut(info_id)
+        .and_then(Object::as_dict_mut)
+        .error("Couldn’t access pdf info dictionary")?;
 
-    fn text_name<'a>(e: &'a Element) -> Option<(Cow<'a, str>, Cow<'a, str>)> {
-        e.text
-            .as_ref()
-            .map(|ref t| (Cow::from(&e.name), Cow::from(&t[..])))
+    fn set_text(info: &mut Dictionary, key: &str, value: Option<String>) {
+        if let Some(v) = value {
+            info.set(key, Object::String(v.into_bytes(), StringFormat::Literal));
+        }
     }
 
-    Ok(metadata)
+    set_text(info, "Title", update.title);
+    set_text(info, "Author", update.author);
+    set_text(info, "Subject", update.subject);
+    set_text(info, "Keywords", update.keywords);
+    set_text(info, "CreationDate", update.creation_date.as_ref().map(format_trail_date));
+    set_text(info, "ModDate", update.mod_date.as_ref().map(format_trail_date));
+
+    file.over(|p| doc.save(p)).chain_err(|| "Couldn’t save file")?;
+
+    Ok(())
+}
+
+/// Validate `file` against `predicate`, as the `check` command.
+pub fn check(file: &PDFName, predicate: PdfPredicate) -> Result<()> {
+    let doc = file.load_doc()?;
+
+    predicate.check(&doc)?;
+
+    println!("{} matches the supplied expectations", file);
+
+    Ok(())
+}
+
+/// The inverse of `pdf::display_trail_date`: format a `DateTime` as a pdf date string,
+/// `D:%Y%m%d%H%M%S` followed by the zone as `+HH'mm'` (or `Z00'00'` for UTC).
+fn format_trail_date(d: &DateTime<FixedOffset>) -> String {
+    let offset = d.offset().local_minus_utc();
+
+    let zone = if offset == 0 {
+        "Z00'00'".to_string()
+    } else {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let offset = offset.abs();
+        format!("{}{:02}'{:02}'", sign, offset / 3600, (offset % 3600) / 60)
+    };
+
+    format!("{}{}", d.format("D:%Y%m%d%H%M%S"), zone)
 }
 
 /*
@@ -244,15 +592,3 @@ fn get_metadata(doc: &Document) -> Result<Vec<(String, String)>> {
  *    }
  *}
  */
-
-/// Identify a document’s page range
-pub fn page_range(doc: &Document) -> Result<RangeInclusive<u32>> {
-    let pages = doc.get_pages();
-
-    match pages.keys().minmax() {
-        // TODO Should assert no error here
-        MinMaxResult::NoElements => Err("No pages in pdf".into()),
-        MinMaxResult::OneElement(&el) => Ok(el..=el),
-        MinMaxResult::MinMax(&min, &max) => Ok(min..=max),
-    }
-}
@@ -23,14 +23,20 @@ mod in_out;
 pub use in_out::*;
 
 mod pdf;
+pub use pdf::*;
+
+mod check;
+pub use check::*;
 
 /// The commands supplied to the library
 #[derive(Debug)]
 pub enum Command {
-    Sel(InputsWithOutputSpec),
-    Zip(InputsWithOutputSpec),
-    Burst(Vec<PDFName>),
+    Sel(InputInOut),
+    Zip(InputInOut),
+    Burst(Vec<PDFName>, bool),
     Info(Vec<PDFName>),
+    SetInfo(PDFName, InfoUpdate),
+    Check(PDFName, PdfPredicate),
 }
 
 impl Display for Command {
@@ -42,7 +48,7 @@ impl Display for Command {
         match self {
             Sel(i) => write!(f, "sel{}", i),
             Zip(i) => write!(f, "zip{}", i),
-            Burst(i) => {
+            Burst(i, _) => {
                 write!(f, "burst")?;
                 i.into_iter().map(|x| write!(f, " {}", x)).collect()
             }
@@ -50,6 +56,8 @@ impl Display for Command {
                 write!(f, "info")?;
                 i.into_iter().map(|x| write!(f, " {}", x)).collect()
             }
+            SetInfo(file, _) => write!(f, "set-info {}", file),
+            Check(file, _) => write!(f, "check {}", file),
         }
     }
 }
@@ -57,8 +65,10 @@ impl Display for Command {
 pub fn padfoot(c: Command) -> Result<()> {
     match c {
         Command::Sel(i) => sel(i),
-        Command::Zip(_) => Err("Not implemented yet".into()),
-        Command::Burst(_) => Err("Not implemented yet".into()),
+        Command::Zip(i) => zip(i),
+        Command::Burst(i, strict) => burst(&i, strict),
         Command::Info(i) => info(&i),
+        Command::SetInfo(file, update) => set_info(&file, update),
+        Command::Check(file, predicate) => check(&file, predicate),
     }
 }
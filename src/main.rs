@@ -11,6 +11,8 @@ use structopt::StructOpt;
 
 extern crate combine;
 
+extern crate chrono;
+
 extern crate padfoot;
 use padfoot::{errors::*, *};
 
@@ -35,21 +37,97 @@ fn main() -> Result<()> {
 /// commands.
 fn process_options(opt: Opt) -> Result<Command> {
     match opt.cmd {
-        OptCmd::Cat { mut inputs, output } => normalize_inputs(&mut inputs, &output, Command::Sel),
+        OptCmd::Cat { mut inputs, output, width, height, strict } => {
+            let size_filter = match (width, height) {
+                (Some(w), Some(h)) => Some((w, h)),
+                _ => None,
+            };
+            normalize_inputs(&mut inputs, &output, size_filter, strict, Command::Sel)
+        }
 
-        OptCmd::Zip { mut inputs, output } => normalize_inputs(&mut inputs, &output, Command::Zip),
+        OptCmd::Zip { mut inputs, output, strict } => {
+            normalize_inputs(&mut inputs, &output, None, strict, Command::Zip)
+        }
 
-        OptCmd::Burst { inputs } => Ok(Command::Burst(inputs)),
+        OptCmd::Burst { inputs, strict } => Ok(Command::Burst(inputs, strict)),
 
         OptCmd::Info { inputs } => Ok(Command::Info(inputs)),
+
+        OptCmd::SetInfo {
+            file,
+            title,
+            author,
+            subject,
+            keywords,
+            creation_date,
+            mod_date,
+        } => {
+            let creation_date = match creation_date {
+                Some(s) => Some(parse_date(&s)?),
+                None => None,
+            };
+            let mod_date = match mod_date {
+                Some(s) => Some(parse_date(&s)?),
+                None => None,
+            };
+
+            Ok(Command::SetInfo(
+                PDFName::new(&file),
+                InfoUpdate {
+                    title,
+                    author,
+                    subject,
+                    keywords,
+                    creation_date,
+                    mod_date,
+                },
+            ))
+        }
+
+        OptCmd::Check {
+            file,
+            pages,
+            page,
+            width,
+            height,
+            creation_date,
+            mod_date,
+        } => {
+            let mut predicate = PdfPredicate::new();
+
+            if let Some(count) = pages {
+                predicate = predicate.expect_page_count(count);
+            }
+
+            if let (Some(page), Some(width), Some(height)) = (page, width, height) {
+                predicate = predicate.expect_page_size(page, width, height);
+            }
+
+            if let Some(s) = creation_date {
+                predicate = predicate.expect_creation_date(parse_date(&s)?);
+            }
+
+            if let Some(s) = mod_date {
+                predicate = predicate.expect_mod_date(parse_date(&s)?);
+            }
+
+            Ok(Command::Check(PDFName::new(&file), predicate))
+        }
     }
 }
 
+/// Parse an RFC 3339 timestamp, as supplied on the `set-info` and `check` command lines.
+fn parse_date(s: &str) -> Result<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(s).chain_err(|| "Couldn’t parse date")
+}
+
 /// It is possible to supply a list of inputs, with the output last (rather than delimited with the
 /// `output` symbol, like pdftk). This ensures that there is exactly one output file.
 fn normalize_inputs(
     inp: &mut Inputs,
     output: &Option<OutputCmd>,
+    size_filter: Option<(f64, f64)>,
+    strict: bool,
     f: impl Fn(InputInOut) -> Command,
 ) -> Result<Command> {
     let inputs = &mut inp.inputs;
@@ -69,7 +147,7 @@ fn normalize_inputs(
     let inputs = group_inputs(&inputs)?;
     let outfile = PDFName::new(&outfile);
 
-    Ok(f(InOut { inputs, outfile }))
+    Ok(f(InOut { inputs, outfile, size_filter, strict }))
 }
 
 /// The input list contains a mix of filenames and page ranges.
@@ -129,6 +207,15 @@ mod options {
             inputs: Inputs,
             #[structopt(subcommand)]
             output: Option<OutputCmd>,
+            /// Restrict the selection to pages of this width, in points (requires `--height`)
+            #[structopt(long = "width")]
+            width: Option<f64>,
+            /// Restrict the selection to pages of this height, in points (requires `--width`)
+            #[structopt(long = "height")]
+            height: Option<f64>,
+            /// Fail on a free or invalid object reference instead of treating it as absent
+            #[structopt(long = "strict")]
+            strict: bool,
         },
 
         #[structopt(name = "zip")]
@@ -137,12 +224,18 @@ mod options {
             inputs: Inputs,
             #[structopt(subcommand)]
             output: Option<OutputCmd>,
+            /// Fail on a free or invalid object reference instead of treating it as absent
+            #[structopt(long = "strict")]
+            strict: bool,
         },
 
         #[structopt(name = "burst")]
         Burst {
             #[structopt(parse(from_os_str))]
             inputs: Vec<PDFName>,
+            /// Fail on a free or invalid object reference instead of treating it as absent
+            #[structopt(long = "strict")]
+            strict: bool,
         },
 
         #[structopt(name = "info")]
@@ -150,6 +243,50 @@ mod options {
             #[structopt(parse(from_os_str))]
             inputs: Vec<PDFName>,
         },
+
+        #[structopt(name = "set-info")]
+        SetInfo {
+            #[structopt(parse(from_os_str))]
+            file: PathBuf,
+            #[structopt(long = "title")]
+            title: Option<String>,
+            #[structopt(long = "author")]
+            author: Option<String>,
+            #[structopt(long = "subject")]
+            subject: Option<String>,
+            #[structopt(long = "keywords")]
+            keywords: Option<String>,
+            /// RFC 3339 timestamp, e.g. 2018-07-10T15:35:07+00:00
+            #[structopt(long = "creation-date")]
+            creation_date: Option<String>,
+            /// RFC 3339 timestamp, e.g. 2018-07-10T15:35:07+00:00
+            #[structopt(long = "mod-date")]
+            mod_date: Option<String>,
+        },
+
+        #[structopt(name = "check")]
+        Check {
+            #[structopt(parse(from_os_str))]
+            file: PathBuf,
+            /// Expected number of pages
+            #[structopt(long = "pages")]
+            pages: Option<usize>,
+            /// Page number to check the size of, in combination with `--width`/`--height`
+            #[structopt(long = "page")]
+            page: Option<u32>,
+            /// Expected page width, in points
+            #[structopt(long = "width")]
+            width: Option<f64>,
+            /// Expected page height, in points
+            #[structopt(long = "height")]
+            height: Option<f64>,
+            /// RFC 3339 timestamp, e.g. 2018-07-10T15:35:07+00:00
+            #[structopt(long = "creation-date")]
+            creation_date: Option<String>,
+            /// RFC 3339 timestamp, e.g. 2018-07-10T15:35:07+00:00
+            #[structopt(long = "mod-date")]
+            mod_date: Option<String>,
+        },
     }
 
     #[derive(Debug, StructOpt)]
@@ -1,10 +1,16 @@
 //! Process pdfs
 
-use std::{collections::btree_set::BTreeSet, ops::RangeInclusive, str, string::String};
+use std::{
+    collections::{btree_set::BTreeSet, HashMap},
+    ops::RangeInclusive,
+    str,
+    string::String,
+};
 
-use chrono::{DateTime, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
 use itertools::{Itertools, MinMaxResult};
 use linked_hash_map::LinkedHashMap;
+use xmltree::Element;
 
 use lopdf::*;
 
@@ -18,10 +24,10 @@ use errors::*;
 /// `PDFTree::link_reference`.
 ///
 /// There are still references within the tree, produced when there are duplicate references in the
-/// source representation, so each object is represented exactly once in the tree directly, and
-/// possibly more often as a reference.
-///
-/// TODO This is _extremely_ hacky, and the solution may be reference counting for sub trees.
+/// source representation, so each object is represented exactly once in the tree directly (as a
+/// `SubTree`, tagged with its `ObjectId` in the *source* document), and possibly more often as a
+/// `Reference` to that same source id. `link_reference` remaps every source id it encounters to a
+/// fresh id in the target document, so folding never reinserts a dangling reference to the source.
 ///
 /// This type recurses mutually with `PDFDictionary`.
 ///
@@ -38,27 +44,91 @@ pub enum PDFTree<'a> {
     Dictionary(Box<PDFDictionary<'a>>),
     Stream(&'a Stream),
     Reference(ObjectId),
-    SubTree(Box<PDFTree<'a>>),
+    SubTree(ObjectId, Box<PDFTree<'a>>),
+}
+
+/// Source document `ObjectId` → target document `ObjectId`.
+///
+/// Built up by `PDFTree::seed_ids` and consulted by `PDFTree::fold` so that every reference to a
+/// given source object resolves to the *same* fresh object in the target document. Callers doing
+/// their own page-by-page extraction (e.g. `sel`/`zip`) build the analogous source page id →
+/// target page id map as they go, and pass it to `copy_outline_items` to remap outline
+/// destinations the same way.
+pub type IdRemap = HashMap<ObjectId, ObjectId>;
+
+/// Options controlling how `PDFTree::new_with` handles references to free or absent objects.
+///
+/// The crate has no notion of a PDF's free list, so it can't tell a genuinely-free object (a
+/// legitimate null per the spec) from a structurally broken reference; these options let the
+/// caller choose how much that distinction matters to them.
+#[derive(Clone, Copy, Debug)]
+pub struct TreeOptions {
+    strict: bool,
+}
+
+impl TreeOptions {
+    /// Lenient options (the default): a reference to a free or absent object resolves as though
+    /// the slot that named it were simply absent.
+    pub fn new() -> Self {
+        TreeOptions { strict: false }
+    }
+
+    /// Fail with the offending `ObjectId` instead of silently treating it as absent.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        TreeOptions::new()
+    }
 }
 
 impl<'a> PDFTree<'a> {
-    /// Create a `PDFTree` from an `lopdf::Object`.
+    /// Create a `PDFTree` from an `lopdf::Object`, in the default lenient mode (see `new_with`).
     ///
     /// As the `lopdf::Object` may include a reference, this function instead takes a reference to a
     /// `lopdf::Document` and an `lopdf::ObjectId`.
     ///
     /// This returns `Err` if the `lopdf::Document` does not contain that `lopdf::ObjectId`.
     pub fn new(oid: ObjectId, doc: &'a Document) -> Result<Self> {
+        PDFTree::new_with(oid, doc, TreeOptions::new())
+    }
+
+    /// Like `new`, but with `options` controlling how references to free or absent objects are
+    /// handled: in lenient mode (`TreeOptions::new()`) they resolve as absent, same as `new`; in
+    /// strict mode (`TreeOptions::new().strict()`) they fail with the offending `ObjectId`.
+    ///
+    /// The root is itself a `SubTree` tagged with `oid`, so a reference back to it from inside
+    /// the tree is remapped like any other.
+    pub fn new_with(oid: ObjectId, doc: &'a Document, options: TreeOptions) -> Result<Self> {
         let o = doc.get_object(oid).error("Couldn’t locate page object")?;
 
-        let mut seen = BTreeSet::new();
-        seen.insert(oid);
+        let mut seen = HashMap::new();
+        seen.insert(oid, false);
 
-        Ok(PDFTree::unfold(doc, &mut seen, o))
+        PDFTree::unfold(doc, &mut seen, o, options)?
+            .map(|tree| PDFTree::SubTree(oid, Box::new(tree)))
+            .error("Object resolved to nothing")
     }
 
-    fn unfold(doc: &'a Document, seen: &mut BTreeSet<ObjectId>, o: &'a Object) -> Self {
-        match o {
+    /// Unfold `o`, or `Ok(None)` if `o` is (or contains, for a `Reference`) a free or absent
+    /// object in lenient mode — the caller treats that slot as simply absent, rather than keeping
+    /// a dangling placeholder.
+    ///
+    /// `seen` records, per source `ObjectId` already encountered, whether it resolved absent
+    /// (`true`) or is in progress / resolved to a real `SubTree` (`false`) — so a *second*
+    /// `Reference` to an object that was dropped as absent the first time is dropped again too,
+    /// rather than emitting a `PDFTree::Reference` with no matching `SubTree` to remap against.
+    fn unfold(
+        doc: &'a Document,
+        seen: &mut HashMap<ObjectId, bool>,
+        o: &'a Object,
+        options: TreeOptions,
+    ) -> Result<Option<Self>> {
+        Ok(Some(match o {
             Object::Null => PDFTree::Null,
             Object::Boolean(b) => PDFTree::Boolean(*b),
             Object::Integer(i) => PDFTree::Integer(*i),
@@ -68,35 +138,90 @@ impl<'a> PDFTree<'a> {
             Object::Array(v) => {
                 let arr = v
                     .iter()
-                    .map(|x| Box::new(PDFTree::unfold(doc, seen, x)))
+                    .map(|x| PDFTree::unfold(doc, seen, x, options))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .flatten()
+                    .map(Box::new)
                     .collect();
                 PDFTree::Array(arr)
             }
             Object::Dictionary(d) => {
-                PDFTree::Dictionary(Box::new(PDFDictionary::new(doc, seen, d)))
+                PDFTree::Dictionary(Box::new(PDFDictionary::new(doc, seen, d, options)?))
             }
             Object::Stream(s) => PDFTree::Stream(&s),
-            Object::Reference(oid) => match seen.contains(oid) {
-                // TODO Note that this is a reference to the object in the *Old* structure
-                true => PDFTree::Reference(*oid),
-                false => {
-                    seen.insert(*oid);
-                    doc.get_object(*oid)
-                        .map(|x| PDFTree::SubTree(Box::new(PDFTree::unfold(doc, seen, x))))
-                        .unwrap_or_else(|| PDFTree::Null)
+            Object::Reference(oid) => match seen.get(oid).cloned() {
+                // A previous `Reference` to the same id already resolved it absent; drop this
+                // occurrence too instead of emitting a dangling `PDFTree::Reference`.
+                Some(true) => return Ok(None),
+                // A reference to an object already unfolded elsewhere in this tree; recorded by
+                // its *source* id, to be remapped to the matching `SubTree`'s target id on fold.
+                Some(false) => PDFTree::Reference(*oid),
+                None => {
+                    seen.insert(*oid, false);
+                    match doc.get_object(*oid) {
+                        Some(x) => match PDFTree::unfold(doc, seen, x, options)? {
+                            Some(tree) => PDFTree::SubTree(*oid, Box::new(tree)),
+                            None => {
+                                seen.insert(*oid, true);
+                                return Ok(None);
+                            }
+                        },
+                        None if options.strict => {
+                            return Err(
+                                format!("Reference to a free or absent object: {:?}", oid).into()
+                            )
+                        }
+                        None => {
+                            seen.insert(*oid, true);
+                            return Ok(None);
+                        }
+                    }
                 }
             },
-        }
+        }))
     }
 
     /// Fold a `PDFTree` into the supplied `lopdf::Document`, providing the `lopdf::ObjectId` of
     /// the `lopdf::Object` corresponding to the root of the `PDFTree`.
     pub fn link_reference(&self, doc: &mut Document) -> ObjectId {
-        let new_object = self.fold(doc);
-        doc.add_object(new_object)
+        let mut remap = IdRemap::new();
+        self.seed_ids(doc, &mut remap);
+
+        match self {
+            // A root `SubTree` (as built by `new_with`) is stored by `fold` under its seeded id.
+            PDFTree::SubTree(oid, _) => {
+                self.fold(doc, &remap);
+                remap[oid]
+            }
+            _ => {
+                let new_object = self.fold(doc, &remap);
+                doc.add_object(new_object)
+            }
+        }
+    }
+
+    /// Allocate a fresh target id for every distinct source `ObjectId` named by a `SubTree` in
+    /// this tree, before any folding happens.
+    ///
+    /// A duplicate `Reference` may be folded before the `SubTree` that defines the same source
+    /// id, so the remapping can't be built lazily during `fold` alone; seeding it up front in one
+    /// traversal guarantees every `Reference`/`SubTree` pair agrees on the target id.
+    fn seed_ids(&self, doc: &mut Document, remap: &mut IdRemap) {
+        match self {
+            PDFTree::Array(v) => v.iter().for_each(|x| x.seed_ids(doc, remap)),
+            PDFTree::Dictionary(d) => d.seed_ids(doc, remap),
+            PDFTree::SubTree(oid, tree) => {
+                if !remap.contains_key(oid) {
+                    remap.insert(*oid, doc.new_object_id());
+                    tree.seed_ids(doc, remap);
+                }
+            }
+            _ => {}
+        }
     }
 
-    fn fold(&self, doc: &mut Document) -> Object {
+    fn fold(&self, doc: &mut Document, remap: &IdRemap) -> Object {
         match self {
             PDFTree::Null => Object::Null,
             PDFTree::Boolean(b) => Object::Boolean(*b),
@@ -105,17 +230,38 @@ impl<'a> PDFTree<'a> {
             PDFTree::Name(v) => Object::Name(v.to_vec()),
             PDFTree::String(v, f) => Object::String(v.to_vec(), (*f).clone()),
             PDFTree::Array(v) => {
-                let arr = v.iter().map(|x| x.fold(doc)).collect();
+                let arr = v.iter().map(|x| x.fold(doc, remap)).collect();
                 Object::Array(arr)
             }
-            PDFTree::Dictionary(d) => d.fold(doc),
+            PDFTree::Dictionary(d) => d.fold(doc, remap),
             PDFTree::Stream(s) => Object::Stream((*s).clone()),
-            // TODO This is wrong; it inserts a reference to an object in the old structure.
-            PDFTree::Reference(oid) => Object::Reference(*oid),
-            PDFTree::SubTree(tree) => {
-                let oid = tree.link_reference(doc);
-                Object::Reference(oid)
+            PDFTree::Reference(oid) => {
+                // Seeded by `seed_ids`, however this `Reference` sits relative to its `SubTree`.
+                Object::Reference(*remap.get(oid).unwrap_or(oid))
             }
+            PDFTree::SubTree(oid, tree) => {
+                let new_id = remap[oid];
+                let object = tree.fold(doc, remap);
+                doc.objects.insert(new_id, object);
+                Object::Reference(new_id)
+            }
+        }
+    }
+}
+
+/// Page attributes that are inheritable from a `Pages` ancestor when a page dictionary omits
+/// them directly. Used to make `/Parent` safe to drop once extracted.
+const INHERITABLE_KEYS: [&str; 4] = ["MediaBox", "CropBox", "Resources", "Rotate"];
+
+/// Resolve an inheritable page attribute, walking up `/Parent` ancestors via `doc.get_dictionary`
+/// until one declares `key` (or the chain runs out).
+fn resolve_inherited<'a>(doc: &'a Document, dict: &'a Dictionary, key: &str) -> Option<&'a Object> {
+    match dict.get(key) {
+        Ok(value) => deref(doc, value).ok(),
+        Err(_) => {
+            let parent = dict.get("Parent").and_then(Object::as_reference).ok()?;
+            let parent_dict = doc.get_dictionary(parent).ok()?;
+            resolve_inherited(doc, parent_dict, key)
         }
     }
 }
@@ -126,30 +272,57 @@ impl<'a> PDFTree<'a> {
 /// This is much like a `lopdf::Dictionary` except it only contains references, and it forms part
 /// of the mutually recursive structure with the `PDFTree`.
 ///
-/// TODO It currently `/Parent` keys. These should be propagated (somehow).
-///
 /// Like for the `PDFTree`, the lifetime corresponds to the lifetime of the associated
 /// `lopdf::Document`.
 pub struct PDFDictionary<'a>(LinkedHashMap<&'a str, PDFTree<'a>>);
 
 impl<'a> PDFDictionary<'a> {
-    fn new(doc: &'a Document, seen: &mut BTreeSet<ObjectId>, d: &'a Dictionary) -> Self {
+    fn new(
+        doc: &'a Document,
+        seen: &mut HashMap<ObjectId, bool>,
+        d: &'a Dictionary,
+        options: TreeOptions,
+    ) -> Result<Self> {
         let mut dict = LinkedHashMap::new();
 
-        d.iter().for_each(|(s, o)| {
+        for (s, o) in d.iter() {
+            // A value that unfolds to `None` named a free or absent object (lenient mode); treat
+            // the key as simply absent rather than keeping a dangling placeholder, matching how
+            // real-world readers recover from xref tables marking root-referenced objects free.
             if s != "Parent" {
-                dict.insert(s.as_ref(), PDFTree::unfold(doc, seen, o));
+                if let Some(tree) = PDFTree::unfold(doc, seen, o, options)? {
+                    dict.insert(s.as_ref(), tree);
+                }
+            }
+        }
+
+        // `/Parent` is dropped above, but `MediaBox`/`CropBox`/`Resources`/`Rotate` are
+        // inheritable: copy each one this dictionary doesn't declare directly from the nearest
+        // `Pages` ancestor that does, so the extracted page stays self-contained without it.
+        if d.get("Parent").is_ok() {
+            for &key in INHERITABLE_KEYS.iter() {
+                if d.get(key).is_err() {
+                    if let Some(value) = resolve_inherited(doc, d, key) {
+                        if let Some(tree) = PDFTree::unfold(doc, seen, value, options)? {
+                            dict.insert(key, tree);
+                        }
+                    }
+                }
             }
-        });
+        }
 
-        PDFDictionary(dict)
+        Ok(PDFDictionary(dict))
     }
 
-    fn fold(&self, doc: &mut Document) -> Object {
+    fn seed_ids(&self, doc: &mut Document, remap: &mut IdRemap) {
+        self.0.values().for_each(|tree| tree.seed_ids(doc, remap));
+    }
+
+    fn fold(&self, doc: &mut Document, remap: &IdRemap) -> Object {
         let mut dict = Dictionary::new();
         self.0
             .iter()
-            .for_each(|(&s, tree)| dict.set(s.clone(), tree.fold(doc)));
+            .for_each(|(&s, tree)| dict.set(s.clone(), tree.fold(doc, remap)));
         Object::Dictionary(dict)
     }
 }
@@ -210,65 +383,194 @@ pub fn page_range(doc: &Document) -> Result<RangeInclusive<u32>> {
     }
 }
 
-/*
- *pub fn get_metadata(doc: &Document) -> Result<Vec<(String, String)>> {
- *    let catalog = doc.catalog().error("Couldn’t access catalog")?;
- *
- *    let metadata = catalog
- *        .get("Metadata")
- *        .and_then(Object::as_reference)
- *        .error("Couldn’t identify metadata")
- *        .and_then(|r| {
- *            doc.get_object(r)
- *                .and_then(Object::as_stream)
- *                .error("Couldn’t access metadata")
- *        })
- *        .map(|s| &s.content)
- *        //.and_then(|s| str::from_utf8(&s[54..]).error("Couldn’t decode utf8"))?;
- *        .and_then(|s| Element::parse(&s[54..]).chain_err(|| "Couldn’t read xml"))
- *        .map(|e| text_names(&e).into_iter().map(|(n,t)| (n.into_owned(), t.into_owned())).collect())?;
- *
- *        //fn decode_stream(s: &Stream) -> Result<content::Content> {
- *            //s.decode_content().error("Couldn’t parse content stream")
- *        //}
- *
- *        //fn chain_leaves<A>(e: &Element) -> impl Iterator<Item = &Element> {
- *            //match e.children[..] {
- *                //[] => iter::once(e),
- *                ////ref cs => cs.iter().fold(iter::empty(), |a, c| a.chain(chain_leaves(c)).collect()),
- *                //// TODO
- *                //ref cs => cs.iter().flat_map(|it| it.clone()a.chain(chain_leaves(c)).collect()),
- *            //}
- *        //}
- *
- *    fn fold_element_leaves<'a, A>(e: &'a Element, f: impl Fn(&'a Element) -> A) -> Vec<A> {
- *        match e.children[..] {
- *            [] => vec![f(e)],
- *            ref cs => cs
- *                .iter()
- *                .fold(vec![], |_a: Vec<A>, c: &Element| fold_element_leaves(c, &f)),
- *        }
- *    }
- *
- *    fn text_names<'a>(el: &'a Element) -> Vec<(Cow<'a, str>, Cow<'a, str>)> {
- *        fold_element_leaves(el, text_name)
- *            .into_iter()
- *            .filter_map(|x| x)
- *            .collect()
- *    }
- *
- *    fn text_name<'a>(e: &'a Element) -> Option<(Cow<'a, str>, Cow<'a, str>)> {
- *        e.text
- *            .as_ref()
- *            .map(|ref t| (Cow::from(&e.name), Cow::from(&t[..])))
- *    }
- *
- *    Ok(metadata)
- *}
- */
+/// A page’s `MediaBox`, the array `[x0 y0 x1 y1]` describing its physical bounds.
+///
+/// `MediaBox` is inheritable: a page dictionary that omits it defers to its nearest `Pages`
+/// ancestor that declares one.
+fn resolve_media_box<'a>(doc: &'a Document, dict: &'a Dictionary) -> Result<&'a Object> {
+    match dict.get("MediaBox") {
+        Ok(mb) => deref(doc, mb),
+        Err(_) => {
+            let parent = dict
+                .get("Parent")
+                .and_then(Object::as_reference)
+                .error("Page has no MediaBox, and no Parent to inherit one from")?;
+            let parent_dict = doc
+                .get_dictionary(parent)
+                .error("Couldn’t locate parent Pages dictionary")?;
+            resolve_media_box(doc, parent_dict)
+        }
+    }
+}
+
+fn deref<'a>(doc: &'a Document, o: &'a Object) -> Result<&'a Object> {
+    match o {
+        Object::Reference(r) => doc.get_object(*r).error("Couldn’t follow reference"),
+        other => Ok(other),
+    }
+}
+
+/// Look up a page’s `MediaBox`, following the page tree’s inheritance rules when the page
+/// dictionary doesn’t declare one directly.
+pub fn media_box(doc: &Document, page_id: ObjectId) -> Result<Object> {
+    let dict = doc
+        .get_dictionary(page_id)
+        .error("Couldn’t locate page dictionary")?;
+
+    resolve_media_box(doc, dict).map(Object::clone)
+}
+
+/// The physical (width, height) in points described by a `MediaBox` array, as returned by
+/// `media_box`.
+///
+/// `MediaBox` entries may be a mix of `Integer` and `Real`, so this tolerates either.
+pub fn media_box_dimensions(mb: &Object) -> Result<(f64, f64)> {
+    fn as_f64(o: &Object) -> Result<f64> {
+        match o {
+            Object::Integer(i) => Ok(*i as f64),
+            Object::Real(f) => Ok(*f as f64),
+            _ => Err("MediaBox entry wasn’t a number".into()),
+        }
+    }
+
+    match mb.as_array().error("MediaBox wasn’t an array")?.as_slice() {
+        [x0, y0, x1, y1] => Ok((
+            (as_f64(x1)? - as_f64(x0)?).abs(),
+            (as_f64(y1)? - as_f64(y0)?).abs(),
+        )),
+        _ => Err("MediaBox didn’t have four entries".into()),
+    }
+}
+
+/// Approximate float equality, tolerating the rounding error typical of `MediaBox` coordinates.
+pub fn approx_eq(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+/// Common paper sizes, in points (1/72 inch), for use with `approx_eq`/`media_box_dimensions`.
+pub mod paper {
+    /// A4 (210mm × 297mm) in points.
+    pub const A4: (f64, f64) = (595.2756, 841.8898);
+}
+
+/// Total number of pages in the document.
+pub fn page_count(doc: &Document) -> usize {
+    doc.get_pages().len()
+}
+
+/// A page’s physical `(width, height)` in points, resolved via its `MediaBox` (honouring
+/// inheritance). `page` is 1-indexed, matching `lopdf::Document::get_pages`.
+pub fn page_size(doc: &Document, page: u32) -> Result<(f64, f64)> {
+    let &oid = doc
+        .get_pages()
+        .get(&page)
+        .error(format!("No such page: {}", page))?;
+
+    media_box_dimensions(&media_box(doc, oid)?)
+}
+
+/// Page numbers, among `nos`, whose `MediaBox` dimensions match `(width, height)` within
+/// `tolerance` points. Pages whose size can’t be determined are dropped, not propagated as an
+/// error.
+///
+/// Composes with a page list already narrowed by `page_ranges` (e.g. a `sel` selection), to
+/// further restrict it to pages of a given physical size.
+pub fn filter_by_size(doc: &Document, nos: &[u32], width: f64, height: f64, tolerance: f64) -> Vec<u32> {
+    let pages = doc.get_pages();
+
+    nos.iter()
+        .cloned()
+        .filter(|no| {
+            pages
+                .get(no)
+                .and_then(|&oid| media_box(doc, oid).ok())
+                .and_then(|mb| media_box_dimensions(&mb).ok())
+                .map_or(false, |(w, h)| {
+                    approx_eq(w, width, tolerance) && approx_eq(h, height, tolerance)
+                })
+        })
+        .collect()
+}
+
+/// Find the start of the XMP packet within a `/Metadata` stream.
+///
+/// The stream is usually preceded by a `<?xpacket begin=...?>` processing instruction; bare
+/// `<x:xmpmeta>` documents (no packet wrapper) are also accepted. Either way, `xmltree` can only
+/// parse from the first `<`.
+fn xmp_packet_start(content: &[u8]) -> Result<usize> {
+    let text = str::from_utf8(content).error("Couldn’t decode metadata as utf8")?;
+
+    text.find("<?xpacket")
+        .or_else(|| text.find("<x:xmpmeta"))
+        .error("Couldn’t locate the start of the XMP packet")
+}
+
+/// Namespace-qualified leaf name and text of an XMP RDF node (e.g. `("dc:title", "Report")`).
+fn leaf_property(e: &Element) -> Option<(String, String)> {
+    let name = match e.prefix {
+        Some(ref prefix) => format!("{}:{}", prefix, e.name),
+        None => e.name.clone(),
+    };
+
+    e.text.as_ref().map(|t| (name, t.clone()))
+}
+
+/// Walk an XMP RDF tree, collecting every leaf (childless) element as a namespace-qualified
+/// property, in document order.
+fn collect_leaves(e: &Element, into: &mut LinkedHashMap<String, String>) {
+    match e.children[..] {
+        [] => {
+            if let Some((name, text)) = leaf_property(e) {
+                into.insert(name, text);
+            }
+        }
+        ref cs => cs.iter().for_each(|c| collect_leaves(c, into)),
+    }
+}
+
+/// The document’s XMP metadata, read from the catalog’s `/Metadata` stream and parsed as RDF,
+/// as namespace-qualified leaf properties (e.g. `dc:title`, `dc:creator`, `xmp:CreateDate`,
+/// `xmp:ModifyDate`, `pdf:Producer`), in document order.
+///
+/// Only properties written as elements with a text value are recorded under their own name. A
+/// property whose value is an RDF container (`rdf:Alt`/`rdf:Seq`/`rdf:Bag`, the usual form of
+/// `dc:title` and `dc:creator`) is recorded under the name of its `rdf:li` leaves, each one
+/// overwriting the last; properties written as attributes of `rdf:Description` are not read.
+///
+/// Returns an empty map, not an error, when the document carries no `/Metadata` stream — XMP is
+/// optional, and many documents only carry the legacy `/Info` dictionary (see `get_trail_info`).
+pub fn get_metadata(doc: &Document) -> LinkedHashMap<String, String> {
+    fn extract(doc: &Document) -> Result<LinkedHashMap<String, String>> {
+        let catalog = doc.catalog().error("Couldn’t access catalog")?;
+
+        let stream = catalog
+            .get("Metadata")
+            .and_then(Object::as_reference)
+            .error("Couldn’t identify metadata")
+            .and_then(|r| {
+                doc.get_object(r)
+                    .and_then(Object::as_stream)
+                    .error("Couldn’t access metadata")
+            })?;
+
+        let content = stream
+            .decompressed_content()
+            .chain_err(|| "Couldn’t decode metadata stream")?;
+
+        let start = xmp_packet_start(&content)?;
+
+        let element = Element::parse(&content[start..]).chain_err(|| "Couldn’t read xml")?;
+
+        let mut properties = LinkedHashMap::new();
+        collect_leaves(&element, &mut properties);
+        Ok(properties)
+    }
+
+    extract(doc).unwrap_or_default()
+}
 
 /// Pretty print a date, formatted in the pdf trailer
-fn display_trail_date(s: &str) -> Result<String> {
+pub(crate) fn display_trail_date(s: &str) -> Result<String> {
     DateTime::parse_from_str(&(s.replace("'", "").replace("Z", "+")), "D:%Y%m%d%H%M%S%z")
         .map(|d| format!("{}", d.format("%a, %d %b %Y %T %z")))
         .or_else(|_| {
@@ -278,6 +580,199 @@ fn display_trail_date(s: &str) -> Result<String> {
         .chain_err(|| "Couldn’t parse date")
 }
 
+/// Parse a pdf trailer date string into a `DateTime`, for comparison against a caller-supplied
+/// expectation (see `check::PdfPredicate`).
+///
+/// A date with no explicit zone (e.g. `D:20170711121931`) is treated as UTC.
+pub fn parse_trail_date(s: &str) -> Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(&(s.replace("'", "").replace("Z", "+")), "D:%Y%m%d%H%M%S%z")
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(s, "D:%Y%m%d%H%M%S")
+                .map(|d| DateTime::from_utc(d, FixedOffset::east(0)))
+        })
+        .chain_err(|| "Couldn’t parse date")
+}
+
+/// Extract and parse a date-valued entry (e.g. `CreationDate`, `ModDate`) from the document’s
+/// `/Info` dictionary.
+fn trail_date(doc: &Document, key: &str) -> Result<DateTime<FixedOffset>> {
+    let value = get_trail_info(doc)?
+        .filter_map(|(k, o)| if k == key { Some(o) } else { None })
+        .next()
+        .error(format!("No {} in pdf info", key))?;
+
+    let raw = match value {
+        Object::String(v, _) => str::from_utf8(v).error("Couldn’t decode date as utf8"),
+        _ => Err(format!("{} wasn’t a string", key).into()),
+    }?;
+
+    parse_trail_date(raw)
+}
+
+/// The document’s `/Info` `CreationDate`, parsed into a structured `DateTime`.
+pub fn creation_date(doc: &Document) -> Result<DateTime<FixedOffset>> {
+    trail_date(doc, "CreationDate")
+}
+
+/// The document’s `/Info` `ModDate`, parsed into a structured `DateTime`.
+pub fn mod_date(doc: &Document) -> Result<DateTime<FixedOffset>> {
+    trail_date(doc, "ModDate")
+}
+
+/// The first item in a document’s `/Outlines` tree (its `/First`), if it has one.
+pub fn outline_first_item(doc: &Document) -> Option<ObjectId> {
+    let outlines_id = doc
+        .catalog()
+        .ok()?
+        .get("Outlines")
+        .and_then(Object::as_reference)
+        .ok()?;
+
+    doc.get_dictionary(outlines_id)
+        .ok()?
+        .get("First")
+        .and_then(Object::as_reference)
+        .ok()
+}
+
+/// The `ObjectId` of the page an outline item’s `/Dest` (or its `/A` `GoTo` action’s `/D`)
+/// targets, if it names one directly.
+///
+/// A named destination (a string or name looked up via the catalog’s `/Dests` tree, rather than
+/// an array naming the page directly) isn’t resolved; such an item is treated as having no
+/// destination, the same as if it named a page that didn’t survive extraction.
+fn outline_dest_page(doc: &Document, item: &Dictionary) -> Option<ObjectId> {
+    fn array_dest_page(doc: &Document, o: &Object) -> Option<ObjectId> {
+        deref(doc, o).ok()?.as_array().ok()?.first()?.as_reference().ok()
+    }
+
+    item.get("Dest")
+        .ok()
+        .and_then(|d| array_dest_page(doc, d))
+        .or_else(|| {
+            let action = deref(doc, item.get("A").ok()?).ok()?.as_dict().ok()?;
+            match action.get("S").ok()? {
+                Object::Name(s) if s.as_slice() == &b"GoTo"[..] => {
+                    array_dest_page(doc, action.get("D").ok()?)
+                }
+                _ => None,
+            }
+        })
+}
+
+/// Link a run of already-copied outline item ids as siblings under `parent` in `new`: wire up
+/// each item’s `/Next`/`/Prev`, and set `parent`’s `/First`/`/Last`/`/Count` to describe the whole
+/// chain (a no-op on `parent` if `items` is empty).
+///
+/// Calling this again over a concatenation of several such runs (e.g. one per input document, as
+/// `sel`/`zip` do when merging outlines) re-links the boundaries between them, since the repeated
+/// internal links it recomputes agree with what the individual runs already set.
+pub fn link_outline_siblings(new: &mut Document, parent: ObjectId, items: &[ObjectId]) {
+    for (&a, &b) in items.iter().zip(items.iter().skip(1)) {
+        if let Some(d) = new.get_object_mut(a).and_then(Object::as_dict_mut) {
+            d.set("Next", b);
+        }
+        if let Some(d) = new.get_object_mut(b).and_then(Object::as_dict_mut) {
+            d.set("Prev", a);
+        }
+    }
+
+    if let (Some(&first), Some(&last)) = (items.first(), items.last()) {
+        if let Some(d) = new.get_object_mut(parent).and_then(Object::as_dict_mut) {
+            d.set("First", first);
+            d.set("Last", last);
+            d.set("Count", items.len() as i64);
+        }
+    }
+}
+
+/// Copy the surviving outline items in the sibling chain starting at `first` (a source-document
+/// `/First` id) into `new`, setting each copy’s `/Parent` to `parent`, remapping its destination
+/// via `kept` (source page id → target page id, as built while extracting pages — see `IdRemap`),
+/// and recursing into `/First` children. An item with its own `/Dest`/`/A` that isn’t in `kept`
+/// is pruned only if none of its children survive either; a purely organizational item (no
+/// `/Dest`/`/A` of its own, e.g. a chapter heading) is kept as long as at least one child does.
+///
+/// Returns the new top-level item ids, already linked as siblings via `link_outline_siblings`, so
+/// the caller can graft them under `parent`’s `/First`…`/Last`, or splice several such runs
+/// together first when merging outlines from multiple inputs.
+pub fn copy_outline_items(
+    doc: &Document,
+    new: &mut Document,
+    kept: &IdRemap,
+    first: ObjectId,
+    parent: ObjectId,
+) -> Vec<ObjectId> {
+    let mut seen = BTreeSet::new();
+    let mut copied = vec![];
+
+    let mut current = Some(first);
+    while let Some(oid) = current {
+        if !seen.insert(oid) {
+            break; // a cyclic /Next chain in a malformed document
+        }
+
+        let item = match doc.get_dictionary(oid) {
+            Ok(item) => item,
+            Err(_) => break,
+        };
+
+        current = item.get("Next").and_then(Object::as_reference).ok();
+
+        if let Some(new_id) = copy_outline_item(doc, new, kept, item, parent) {
+            copied.push(new_id);
+        }
+    }
+
+    link_outline_siblings(new, parent, &copied);
+
+    copied
+}
+
+fn copy_outline_item(
+    doc: &Document,
+    new: &mut Document,
+    kept: &IdRemap,
+    item: &Dictionary,
+    parent: ObjectId,
+) -> Option<ObjectId> {
+    let new_page = outline_dest_page(doc, item).and_then(|page| kept.get(&page).cloned());
+
+    let new_id = new.new_object_id();
+
+    let kids = item
+        .get("First")
+        .and_then(Object::as_reference)
+        .ok()
+        .map(|first| copy_outline_items(doc, new, kept, first, new_id))
+        .unwrap_or_default();
+
+    // A purely organizational bookmark (e.g. a chapter heading) has no `/Dest`/`/A` of its own;
+    // keep it only if it still has surviving children to hold onto, otherwise prune it (and its
+    // now-empty `kids`, already pruned by the recursive call above).
+    if new_page.is_none() && kids.is_empty() {
+        return None;
+    }
+
+    let mut dict = Dictionary::new();
+    dict.set("Title", item.get("Title").ok().cloned().unwrap_or(Object::Null));
+    dict.set("Parent", parent);
+
+    if let Some(new_page) = new_page {
+        dict.set("Dest", Object::Array(vec![Object::Reference(new_page), "Fit".into()]));
+    }
+
+    if let (Some(&kids_first), Some(&kids_last)) = (kids.first(), kids.last()) {
+        dict.set("First", kids_first);
+        dict.set("Last", kids_last);
+        dict.set("Count", kids.len() as i64);
+    }
+
+    new.objects.insert(new_id, Object::Dictionary(dict));
+
+    Some(new_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,85 @@
+//! Integration tests for `PDFTree`'s extraction/remapping machinery (`PDFTree::new`,
+//! `PDFTree::link_reference`), built directly against in-memory `lopdf::Document`s.
+
+extern crate lopdf;
+extern crate padfoot;
+
+use lopdf::*;
+
+use padfoot::PDFTree;
+
+#[test]
+fn duplicate_reference_to_a_present_object_remaps_to_the_same_target_id() {
+    let mut doc = Document::new();
+
+    let child_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+    });
+
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Annots" => vec![Object::Reference(child_id), Object::Reference(child_id)],
+    });
+
+    let tree = PDFTree::new(page_id, &doc).unwrap();
+
+    let mut new_doc = Document::new();
+    let new_page_id = tree.link_reference(&mut new_doc);
+
+    let annots = new_doc
+        .get_dictionary(new_page_id)
+        .unwrap()
+        .get("Annots")
+        .unwrap()
+        .as_array()
+        .unwrap();
+
+    assert_eq!(annots.len(), 2);
+
+    let ids: Vec<ObjectId> = annots.iter().map(|o| o.as_reference().unwrap()).collect();
+
+    assert_eq!(
+        ids[0], ids[1],
+        "two references to the same source object should remap to the same target id"
+    );
+    assert!(
+        new_doc.get_object(ids[0]).is_some(),
+        "the remapped reference should resolve within the target document"
+    );
+}
+
+#[test]
+fn duplicate_reference_to_a_free_object_is_dropped_in_lenient_mode() {
+    let mut doc = Document::new();
+
+    // Never inserted into `doc.objects`, so it's free/absent from `doc`'s point of view.
+    let free_id: ObjectId = (999, 0);
+
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Annots" => vec![Object::Reference(free_id), Object::Reference(free_id)],
+    });
+
+    // Lenient by default: neither reference to `free_id` should be an error.
+    let tree = PDFTree::new(page_id, &doc).unwrap();
+
+    let mut new_doc = Document::new();
+    let new_page_id = tree.link_reference(&mut new_doc);
+
+    let new_page = new_doc.get_dictionary(new_page_id).unwrap();
+
+    // Both occurrences of the free reference should have been dropped, not just the first;
+    // whatever is left in `Annots` (including the key being absent entirely) must resolve inside
+    // `new_doc` rather than pointing back at the source document's id space.
+    if let Ok(annots) = new_page.get("Annots").and_then(Object::as_array) {
+        for o in annots {
+            if let Ok(r) = o.as_reference() {
+                assert!(
+                    new_doc.get_object(r).is_some(),
+                    "Annots contains a reference absent from the target document: {:?}",
+                    r
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Integration tests for the crate's typed inspection API (`page_count`, `page_size`,
+//! `creation_date`, `mod_date`) and the `PdfPredicate` verification layer, built directly
+//! against an in-memory `lopdf::Document` rather than a fixture file.
+
+extern crate chrono;
+extern crate lopdf;
+extern crate padfoot;
+
+use chrono::DateTime;
+
+use lopdf::*;
+
+use padfoot::{creation_date, mod_date, page_count, page_size, PdfPredicate};
+
+/// A single A4 page, with `/Info` `CreationDate`/`ModDate` set, matching the dates used by
+/// `pdf::tests::test_display_trail_date`.
+fn build_doc() -> Document {
+    let mut doc = Document::new();
+
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+    });
+
+    let pages_id = doc.add_object(dictionary! {
+        "Type" => "Pages",
+        "Kids" => vec![page_id.into()],
+        "Count" => 1,
+    });
+
+    doc.objects
+        .get_mut(&page_id)
+        .and_then(Object::as_dict_mut)
+        .map(|d| d.set("Parent", pages_id));
+
+    let info_id = doc.add_object(dictionary! {
+        "CreationDate" => Object::String(b"D:20170712171035+01'00'".to_vec(), StringFormat::Literal),
+        "ModDate" => Object::String(b"D:20180710153507Z00'00'".to_vec(), StringFormat::Literal),
+    });
+    doc.trailer.set("Info", info_id);
+
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    doc
+}
+
+#[test]
+fn typed_queries_match_fixture() {
+    let doc = build_doc();
+
+    assert_eq!(page_count(&doc), 1);
+    assert_eq!(page_size(&doc, 1).unwrap(), (595.0, 842.0));
+
+    assert_eq!(
+        creation_date(&doc).unwrap(),
+        DateTime::parse_from_rfc3339("2017-07-12T17:10:35+01:00").unwrap()
+    );
+    assert_eq!(
+        mod_date(&doc).unwrap(),
+        DateTime::parse_from_rfc3339("2018-07-10T15:35:07+00:00").unwrap()
+    );
+}
+
+#[test]
+fn predicate_accepts_matching_fixture() {
+    let doc = build_doc();
+
+    let predicate = PdfPredicate::new()
+        .expect_page_count(1)
+        .expect_page_size(1, 595.0, 842.0)
+        .expect_creation_date(DateTime::parse_from_rfc3339("2017-07-12T17:10:35+01:00").unwrap());
+
+    assert!(predicate.check(&doc).is_ok());
+}
+
+#[test]
+fn predicate_rejects_wrong_page_count() {
+    let doc = build_doc();
+
+    let predicate = PdfPredicate::new().expect_page_count(2);
+
+    assert!(predicate.check(&doc).is_err());
+}